@@ -0,0 +1,330 @@
+//! Parses Roblox's official `API-Dump.json` format (as produced by Roblox
+//! Studio's `-API` command line flag) into a [`ReflectionDatabase`].
+
+use std::{borrow::Cow, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::{
+    ClassDescriptor, EnumDescriptor, PropertyDescriptor, PropertyKind, PropertyTags, PropertyType,
+    ReflectionDatabase, Scriptability, VariantType,
+};
+
+impl ReflectionDatabase<'static> {
+    /// Builds a reflection database out of the contents of Roblox's official
+    /// `API-Dump.json`.
+    pub fn from_api_dump(source: &str) -> serde_json::Result<Self> {
+        let dump: Dump = serde_json::from_str(source)?;
+        Ok(dump.into_database())
+    }
+}
+
+impl Dump {
+    fn into_database(self) -> ReflectionDatabase<'static> {
+        let mut database = ReflectionDatabase::new();
+
+        for dump_enum in self.enums {
+            let mut descriptor = EnumDescriptor::new(dump_enum.name.clone());
+
+            for item in dump_enum.items {
+                descriptor.items.insert(Cow::Owned(item.name), item.value);
+            }
+
+            database.enums.insert(Cow::Owned(dump_enum.name), descriptor);
+        }
+
+        for dump_class in self.classes {
+            let mut descriptor = ClassDescriptor::new(dump_class.name.clone());
+
+            // The dump marks classes with no superclass using a sentinel
+            // name instead of omitting the field.
+            if dump_class.superclass != "<<<ROOT>>>" {
+                descriptor.superclass = Some(Cow::Owned(dump_class.superclass));
+            }
+
+            for member in dump_class.members {
+                if let DumpMember::Property {
+                    name,
+                    security,
+                    tags,
+                    value_type,
+                } = member
+                {
+                    // Skip properties whose type this crate doesn't know how
+                    // to represent rather than mislabeling them; an
+                    // unrecognized property already passes through
+                    // `ReflectionDatabase::canonicalize` unchanged.
+                    let value_type = match value_type.into_property_type() {
+                        Some(value_type) => value_type,
+                        None => continue,
+                    };
+
+                    let tags = tags_from_dump(&tags);
+
+                    let mut property = PropertyDescriptor::new(name.clone());
+                    property.scriptability = scriptability_from_dump(&security, tags);
+                    property.tags = tags;
+                    property.value_type = value_type;
+                    property.kind = PropertyKind::Canonical;
+
+                    descriptor.properties.insert(Cow::Owned(name), property);
+                }
+            }
+
+            database.classes.insert(Cow::Owned(dump_class.name), descriptor);
+        }
+
+        database
+    }
+}
+
+fn scriptability_from_dump(security: &DumpSecurity, tags: PropertyTags) -> Scriptability {
+    if tags.contains(PropertyTags::NOT_SCRIPTABLE) {
+        return Scriptability::None;
+    }
+
+    let readable = security.read == "None";
+    let writable = security.write == "None";
+
+    match (readable, writable) {
+        (true, true) => Scriptability::ReadWrite,
+        (true, false) => Scriptability::Read,
+        (false, true) => Scriptability::Write,
+        (false, false) => Scriptability::None,
+    }
+}
+
+fn tags_from_dump(tags: &[String]) -> PropertyTags {
+    tags.iter()
+        .filter_map(|tag| PropertyTags::from_str(tag).ok())
+        .fold(PropertyTags::empty(), |acc, tag| acc | tag)
+}
+
+/// Maps the `Name` of a dump's `ValueType` onto the `VariantType` used to
+/// represent it at runtime. Returns `None` for names this crate doesn't
+/// recognize yet so the caller can skip the property instead of mislabeling
+/// it as some other type.
+fn parse_variant_type(name: &str) -> Option<VariantType> {
+    Some(match name {
+        "string" => VariantType::String,
+        "bool" => VariantType::Bool,
+        "int" => VariantType::Int32,
+        "int64" => VariantType::Int64,
+        "float" => VariantType::Float32,
+        "double" => VariantType::Float64,
+        "Vector2" => VariantType::Vector2,
+        "Vector2int16" => VariantType::Vector2int16,
+        "Vector3" => VariantType::Vector3,
+        "Vector3int16" => VariantType::Vector3int16,
+        "Color3" => VariantType::Color3,
+        "Color3uint8" => VariantType::Color3uint8,
+        "BrickColor" => VariantType::BrickColor,
+        "CFrame" => VariantType::CFrame,
+        "UDim" => VariantType::UDim,
+        "UDim2" => VariantType::UDim2,
+        "Rect" => VariantType::Rect,
+        "Ray" => VariantType::Ray,
+        "NumberRange" => VariantType::NumberRange,
+        "NumberSequence" => VariantType::NumberSequence,
+        "ColorSequence" => VariantType::ColorSequence,
+        "PhysicalProperties" => VariantType::PhysicalProperties,
+        "Content" | "ContentId" => VariantType::Content,
+        "BinaryString" => VariantType::BinaryString,
+        "SharedString" => VariantType::SharedString,
+        "Tags" => VariantType::Tags,
+        "Axes" => VariantType::Axes,
+        "Faces" => VariantType::Faces,
+        "UniqueId" => VariantType::UniqueId,
+        "Font" => VariantType::Font,
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct Dump {
+    #[serde(rename = "Classes")]
+    classes: Vec<DumpClass>,
+
+    #[serde(rename = "Enums")]
+    enums: Vec<DumpEnum>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DumpClass {
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[serde(rename = "Superclass")]
+    superclass: String,
+
+    #[serde(rename = "Members")]
+    members: Vec<DumpMember>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "MemberType")]
+enum DumpMember {
+    Property {
+        #[serde(rename = "Name")]
+        name: String,
+
+        #[serde(rename = "Security")]
+        security: DumpSecurity,
+
+        #[serde(rename = "Tags", default)]
+        tags: Vec<String>,
+
+        #[serde(rename = "ValueType")]
+        value_type: DumpValueType,
+    },
+
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct DumpSecurity {
+    #[serde(rename = "Read")]
+    read: String,
+
+    #[serde(rename = "Write")]
+    write: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DumpValueType {
+    #[serde(rename = "Category")]
+    category: String,
+
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+impl DumpValueType {
+    /// Returns `None` if this value type isn't one this crate knows how to
+    /// represent, so the caller can skip the property instead of recording
+    /// the wrong type for it.
+    fn into_property_type<'a>(self) -> Option<PropertyType<'a>> {
+        match self.category.as_str() {
+            "Enum" => Some(PropertyType::Enum(Cow::Owned(self.name))),
+            // Properties that reference another instance (`Parent`,
+            // `Parent.Workspace`, and friends) are reported as a reference
+            // to a class rather than a `DataType`.
+            "Class" => Some(PropertyType::Data(VariantType::Ref)),
+            _ => parse_variant_type(&self.name).map(PropertyType::Data),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DumpEnum {
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[serde(rename = "Items")]
+    items: Vec<DumpEnumItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DumpEnumItem {
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[serde(rename = "Value")]
+    value: u32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DUMP: &str = r#"{
+        "Classes": [
+            {
+                "Name": "Part",
+                "Superclass": "<<<ROOT>>>",
+                "Members": [
+                    {
+                        "MemberType": "Property",
+                        "Name": "Material",
+                        "Security": { "Read": "None", "Write": "None" },
+                        "Tags": [],
+                        "ValueType": { "Category": "Enum", "Name": "Material" }
+                    },
+                    {
+                        "MemberType": "Property",
+                        "Name": "Parent",
+                        "Security": { "Read": "None", "Write": "None" },
+                        "Tags": [],
+                        "ValueType": { "Category": "Class", "Name": "Instance" }
+                    },
+                    {
+                        "MemberType": "Property",
+                        "Name": "InternalField",
+                        "Security": { "Read": "None", "Write": "None" },
+                        "Tags": ["NotScriptable"],
+                        "ValueType": { "Category": "Primitive", "Name": "bool" }
+                    },
+                    {
+                        "MemberType": "Property",
+                        "Name": "Unrepresentable",
+                        "Security": { "Read": "None", "Write": "None" },
+                        "Tags": [],
+                        "ValueType": { "Category": "DataType", "Name": "SomeFutureType" }
+                    },
+                    {
+                        "MemberType": "Function",
+                        "Name": "Destroy"
+                    }
+                ]
+            }
+        ],
+        "Enums": [
+            {
+                "Name": "Material",
+                "Items": [
+                    { "Name": "Plastic", "Value": 256 },
+                    { "Name": "Wood", "Value": 512 }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parses_classes_and_enums() {
+        let database = ReflectionDatabase::from_api_dump(DUMP).unwrap();
+
+        let material = database.enums.get("Material").unwrap();
+        assert_eq!(material.items.get("Plastic"), Some(&256));
+        assert_eq!(material.items.get("Wood"), Some(&512));
+
+        let part = database.classes.get("Part").unwrap();
+        assert!(part.superclass.is_none());
+
+        let material_property = part.properties.get("Material").unwrap();
+        match &material_property.value_type {
+            PropertyType::Enum(name) => assert_eq!(name.as_ref(), "Material"),
+            other => panic!("expected an enum property type, got {:?}", other),
+        }
+
+        let parent_property = part.properties.get("Parent").unwrap();
+        assert!(matches!(
+            parent_property.value_type,
+            PropertyType::Data(VariantType::Ref)
+        ));
+
+        let internal_property = part.properties.get("InternalField").unwrap();
+        assert!(matches!(
+            internal_property.scriptability,
+            Scriptability::None
+        ));
+        assert!(internal_property.tags.contains(PropertyTags::NOT_SCRIPTABLE));
+
+        // A member that isn't a Property (e.g. Function) is ignored.
+        assert!(!part.properties.contains_key("Destroy"));
+
+        // A property whose type this crate doesn't recognize is skipped
+        // rather than mislabeled.
+        assert!(!part.properties.contains_key("Unrepresentable"));
+    }
+}
@@ -1,13 +1,40 @@
-use std::{borrow::Cow, collections::HashMap, str::FromStr};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryFrom,
+    fmt,
+    hash::Hash,
+    str::FromStr,
+};
 
-use rbx_types::{Variant, VariantType};
-use serde::{Deserialize, Serialize};
+use rbx_types::{Color3, Enum, Variant, VariantType, Vector3, Vector3int16};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+mod api_dump;
+
+/// Serializes a `HashMap` by first collecting it into a `BTreeMap`, so that
+/// the output is sorted by key and therefore reproducible between runs.
+/// Without this, diffing or content-hashing a generated database is useless
+/// because the same data can serialize differently each time.
+fn ordered_map<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    K: Eq + Hash + Ord + Serialize,
+    V: Serialize,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct ReflectionDatabase<'a> {
     pub version: [u32; 4],
+
+    #[serde(serialize_with = "ordered_map")]
     pub classes: HashMap<Cow<'a, str>, ClassDescriptor<'a>>,
+
+    #[serde(default, serialize_with = "ordered_map")]
+    pub enums: HashMap<Cow<'a, str>, EnumDescriptor<'a>>,
 }
 
 impl<'a> ReflectionDatabase<'a> {
@@ -15,10 +42,310 @@ impl<'a> ReflectionDatabase<'a> {
         Self {
             version: [0, 0, 0, 0],
             classes: HashMap::new(),
+            enums: HashMap::new(),
+        }
+    }
+
+    /// Finds the property with the given name on the given class, following
+    /// the `superclass` chain upwards if the class itself doesn't define it.
+    pub fn find_property(&self, class: &str, property: &str) -> Option<&PropertyDescriptor<'a>> {
+        let mut visited = HashSet::new();
+        let mut current = class;
+
+        loop {
+            if !visited.insert(current.to_owned()) {
+                return None;
+            }
+
+            let descriptor = self.classes.get(current)?;
+
+            if let Some(property) = descriptor.properties.get(property) {
+                return Some(property);
+            }
+
+            current = descriptor.superclass.as_deref()?;
+        }
+    }
+
+    /// Finds the default value of the given property on the given class,
+    /// following the `superclass` chain upwards if the class itself doesn't
+    /// define it.
+    pub fn find_default(&self, class: &str, property: &str) -> Option<&Variant> {
+        let mut visited = HashSet::new();
+        let mut current = class;
+
+        loop {
+            if !visited.insert(current.to_owned()) {
+                return None;
+            }
+
+            let descriptor = self.classes.get(current)?;
+
+            if let Some(default) = descriptor.default_properties.get(property) {
+                return Some(default);
+            }
+
+            current = descriptor.superclass.as_deref()?;
+        }
+    }
+
+    /// Resolves a type-ambiguous value read from a legacy file format into
+    /// the canonical type declared for the given property, coercing between
+    /// types that the binary/XML formats can't tell apart on their own (for
+    /// example, an `Int32` that's really a `Float32`, or a `Vector3` that's
+    /// really a `Color3`).
+    ///
+    /// If the property isn't known to the database, `raw` is returned
+    /// unchanged so that unrecognized properties still round-trip.
+    pub fn canonicalize(
+        &self,
+        class: &str,
+        property: &str,
+        raw: Variant,
+    ) -> Result<Variant, ResolveError> {
+        let descriptor = match self.find_property(class, property) {
+            Some(descriptor) => descriptor,
+            None => return Ok(raw),
+        };
+
+        match &descriptor.value_type {
+            PropertyType::Data(expected) => coerce_data(class, property, raw, *expected),
+            PropertyType::Enum(enum_name) => self.coerce_enum(class, property, enum_name, raw),
+        }
+    }
+
+    fn coerce_enum(
+        &self,
+        class: &str,
+        property: &str,
+        enum_name: &str,
+        raw: Variant,
+    ) -> Result<Variant, ResolveError> {
+        let mismatch = || ResolveError::TypeMismatch {
+            class: class.to_owned(),
+            property: property.to_owned(),
+            expected: VariantType::Enum,
+            actual: raw.ty(),
+        };
+
+        // Only accept numeric values that round-trip exactly into a u32;
+        // a negative or fractional raw value isn't a legal enum item and
+        // must be rejected rather than silently wrapped or truncated.
+        let value = match &raw {
+            Variant::Enum(value) => value.to_u32(),
+            Variant::Int32(value) => u32::try_from(*value).map_err(|_| mismatch())?,
+            Variant::Int64(value) => u32::try_from(*value).map_err(|_| mismatch())?,
+            Variant::Float32(value) => {
+                let as_u32 = *value as u32;
+                if f64::from(as_u32) == f64::from(*value) {
+                    as_u32
+                } else {
+                    return Err(mismatch());
+                }
+            }
+            _ => return Err(mismatch()),
+        };
+
+        let is_known = match self.enums.get(enum_name) {
+            Some(descriptor) => descriptor.items.values().any(|item| *item == value),
+            // If the enum itself isn't in the database, there's nothing to
+            // validate against; let the value pass through.
+            None => true,
+        };
+
+        if !is_known {
+            return Err(ResolveError::UnknownEnumItem {
+                enum_name: enum_name.to_owned(),
+                value,
+            });
+        }
+
+        Ok(Variant::Enum(Enum::from_u32(value)))
+    }
+}
+
+fn coerce_data(
+    class: &str,
+    property: &str,
+    raw: Variant,
+    expected: VariantType,
+) -> Result<Variant, ResolveError> {
+    if raw.ty() == expected {
+        return Ok(raw);
+    }
+
+    // Widening conversions (more bits, same "kind" of number) are always
+    // exact. Narrowing conversions can lose information, so they're only
+    // accepted if the value round-trips back to its original representation
+    // unchanged; otherwise the property is reporting a value this property's
+    // declared type can't actually hold.
+    let coerced = match (&raw, expected) {
+        (Variant::Int32(value), VariantType::Int64) => Some(Variant::Int64(i64::from(*value))),
+        (Variant::Int32(value), VariantType::Float32) => {
+            i32_to_f32_exact(*value).map(Variant::Float32)
+        }
+        (Variant::Int32(value), VariantType::Float64) => {
+            Some(Variant::Float64(f64::from(*value)))
+        }
+        (Variant::Int64(value), VariantType::Int32) => {
+            i32::try_from(*value).ok().map(Variant::Int32)
+        }
+        (Variant::Int64(value), VariantType::Float32) => {
+            i64_to_f32_exact(*value).map(Variant::Float32)
+        }
+        (Variant::Int64(value), VariantType::Float64) => {
+            i64_to_f64_exact(*value).map(Variant::Float64)
+        }
+        (Variant::Float32(value), VariantType::Float64) => {
+            Some(Variant::Float64(f64::from(*value)))
+        }
+        (Variant::Float32(value), VariantType::Int32) => {
+            f32_to_i32_exact(*value).map(Variant::Int32)
+        }
+        (Variant::Float32(value), VariantType::Int64) => {
+            f32_to_i64_exact(*value).map(Variant::Int64)
+        }
+        (Variant::Float64(value), VariantType::Float32) => {
+            f64_to_f32_exact(*value).map(Variant::Float32)
+        }
+        (Variant::Vector3(value), VariantType::Color3) => {
+            Some(Variant::Color3(Color3::new(value.x, value.y, value.z)))
+        }
+        (Variant::Vector3(value), VariantType::Vector3int16) => match (
+            f32_to_i16_exact(value.x),
+            f32_to_i16_exact(value.y),
+            f32_to_i16_exact(value.z),
+        ) {
+            (Some(x), Some(y), Some(z)) => Some(Variant::Vector3int16(Vector3int16::new(x, y, z))),
+            _ => None,
+        },
+        (Variant::Color3(value), VariantType::Vector3) => {
+            Some(Variant::Vector3(Vector3::new(value.r, value.g, value.b)))
+        }
+        (Variant::Vector3int16(value), VariantType::Vector3) => Some(Variant::Vector3(
+            Vector3::new(f32::from(value.x), f32::from(value.y), f32::from(value.z)),
+        )),
+        (Variant::Vector3int16(value), VariantType::Color3) => Some(Variant::Color3(
+            Color3::new(f32::from(value.x), f32::from(value.y), f32::from(value.z)),
+        )),
+        _ => None,
+    };
+
+    coerced.ok_or_else(|| ResolveError::TypeMismatch {
+        class: class.to_owned(),
+        property: property.to_owned(),
+        expected,
+        actual: raw.ty(),
+    })
+}
+
+fn i32_to_f32_exact(value: i32) -> Option<f32> {
+    let as_float = value as f32;
+    if f64::from(as_float) == f64::from(value) {
+        Some(as_float)
+    } else {
+        None
+    }
+}
+
+fn i64_to_f32_exact(value: i64) -> Option<f32> {
+    let as_float = value as f32;
+    if as_float as i128 == i128::from(value) {
+        Some(as_float)
+    } else {
+        None
+    }
+}
+
+fn i64_to_f64_exact(value: i64) -> Option<f64> {
+    let as_float = value as f64;
+    if as_float as i128 == i128::from(value) {
+        Some(as_float)
+    } else {
+        None
+    }
+}
+
+fn f32_to_i32_exact(value: f32) -> Option<i32> {
+    let as_int = value as i32;
+    if f64::from(as_int) == f64::from(value) {
+        Some(as_int)
+    } else {
+        None
+    }
+}
+
+fn f32_to_i64_exact(value: f32) -> Option<i64> {
+    let as_int = value as i64;
+    if as_int as f32 == value && value as i128 == i128::from(as_int) {
+        Some(as_int)
+    } else {
+        None
+    }
+}
+
+fn f32_to_i16_exact(value: f32) -> Option<i16> {
+    let as_int = value as i16;
+    if as_int as f32 == value {
+        Some(as_int)
+    } else {
+        None
+    }
+}
+
+fn f64_to_f32_exact(value: f64) -> Option<f32> {
+    let as_float = value as f32;
+    if as_float as f64 == value {
+        Some(as_float)
+    } else {
+        None
+    }
+}
+
+/// An error produced while resolving a type-ambiguous value against the
+/// reflection database.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ResolveError {
+    /// The declared type for the property couldn't be reconciled with the
+    /// type of the value that was actually found.
+    TypeMismatch {
+        class: String,
+        property: String,
+        expected: VariantType,
+        actual: VariantType,
+    },
+
+    /// The property is an enum, but the numeric value found doesn't match
+    /// any item defined on that enum.
+    UnknownEnumItem { enum_name: String, value: u32 },
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::TypeMismatch {
+                class,
+                property,
+                expected,
+                actual,
+            } => write!(
+                formatter,
+                "property {}.{} is declared as {:?}, but a {:?} value was found and no \
+                 conversion between the two exists",
+                class, property, expected, actual
+            ),
+            ResolveError::UnknownEnumItem { enum_name, value } => write!(
+                formatter,
+                "{} is not a known value for the enum {}",
+                value, enum_name
+            ),
         }
     }
 }
 
+impl std::error::Error for ResolveError {}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct ClassDescriptor<'a> {
@@ -27,7 +354,10 @@ pub struct ClassDescriptor<'a> {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub superclass: Option<Cow<'a, str>>,
 
+    #[serde(serialize_with = "ordered_map")]
     pub properties: HashMap<Cow<'a, str>, PropertyDescriptor<'a>>,
+
+    #[serde(serialize_with = "ordered_map")]
     pub default_properties: HashMap<Cow<'a, str>, Variant>,
 }
 
@@ -42,11 +372,41 @@ impl<'a> ClassDescriptor<'a> {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EnumDescriptor<'a> {
+    pub name: Cow<'a, str>,
+
+    #[serde(serialize_with = "ordered_map")]
+    pub items: HashMap<Cow<'a, str>, u32>,
+}
+
+impl<'a> EnumDescriptor<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            items: HashMap::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct PropertyDescriptor<'a> {
     pub name: Cow<'a, str>,
     pub scriptability: Scriptability,
+
+    #[serde(default)]
+    pub value_type: PropertyType<'a>,
+
+    #[serde(default = "PropertyTags::empty")]
+    pub tags: PropertyTags,
+
+    #[serde(default)]
+    pub default_value: Option<Variant>,
+
+    #[serde(default)]
+    pub kind: PropertyKind<'a>,
 }
 
 impl<'a> PropertyDescriptor<'a> {
@@ -54,10 +414,35 @@ impl<'a> PropertyDescriptor<'a> {
         Self {
             name: name.into(),
             scriptability: Scriptability::None,
+            value_type: PropertyType::Data(VariantType::String),
+            tags: PropertyTags::empty(),
+            default_value: None,
+            kind: PropertyKind::Canonical,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum PropertyKind<'a> {
+    /// The property is the canonical, storage form of its value.
+    Canonical,
+
+    /// The property is a different name for a canonical property, such as
+    /// `BrickColor` being an alias of `Color3uint8`.
+    Alias { alias_for: Cow<'a, str> },
+
+    /// The property only exists in serialized files and has no runtime
+    /// representation; it's usually consumed by `Alias` properties instead.
+    Serialized,
+}
+
+impl<'a> Default for PropertyKind<'a> {
+    fn default() -> Self {
+        PropertyKind::Canonical
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum PropertyType<'a> {
@@ -68,6 +453,12 @@ pub enum PropertyType<'a> {
     Enum(Cow<'a, str>),
 }
 
+impl<'a> Default for PropertyType<'a> {
+    fn default() -> Self {
+        PropertyType::Data(VariantType::String)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum Scriptability {
@@ -136,6 +527,29 @@ macro_rules! bitterflag {
                 }
             }
         }
+
+        // bitflags doesn't derive Serialize/Deserialize on its own, so these
+        // are implemented by hand in terms of the underlying bit width.
+        // Unknown bits are truncated rather than rejected so that databases
+        // written by a newer version of this crate still deserialize here.
+        impl Serialize for $struct_name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                self.bits().serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $struct_name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let bits = $width::deserialize(deserializer)?;
+                Ok(Self::from_bits_truncate(bits))
+            }
+        }
     };
 }
 
@@ -206,3 +620,147 @@ impl FromStr for PropertyTags {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn database_with_hierarchy() -> ReflectionDatabase<'static> {
+        let mut database = ReflectionDatabase::new();
+
+        let mut base = ClassDescriptor::new("BasePart");
+        base.default_properties
+            .insert(Cow::Borrowed("Transparency"), Variant::Float32(0.0));
+
+        let mut size_property = PropertyDescriptor::new("Size");
+        size_property.value_type = PropertyType::Data(VariantType::Vector3);
+        base.properties.insert(Cow::Borrowed("Size"), size_property);
+
+        let mut part = ClassDescriptor::new("Part");
+        part.superclass = Some(Cow::Borrowed("BasePart"));
+
+        database.classes.insert(Cow::Borrowed("BasePart"), base);
+        database.classes.insert(Cow::Borrowed("Part"), part);
+
+        database
+    }
+
+    #[test]
+    fn find_property_walks_superclass_chain() {
+        let database = database_with_hierarchy();
+
+        let property = database
+            .find_property("Part", "Size")
+            .expect("Size should be inherited from BasePart");
+        assert!(matches!(
+            property.value_type,
+            PropertyType::Data(VariantType::Vector3)
+        ));
+
+        assert!(database.find_property("Part", "DoesNotExist").is_none());
+    }
+
+    #[test]
+    fn find_default_walks_superclass_chain() {
+        let database = database_with_hierarchy();
+
+        let default = database
+            .find_default("Part", "Transparency")
+            .expect("Transparency should be inherited from BasePart");
+        assert!(matches!(default, Variant::Float32(_)));
+    }
+
+    #[test]
+    fn find_property_terminates_on_superclass_cycle() {
+        let mut database = ReflectionDatabase::new();
+
+        let mut a = ClassDescriptor::new("A");
+        a.superclass = Some(Cow::Borrowed("B"));
+
+        let mut b = ClassDescriptor::new("B");
+        b.superclass = Some(Cow::Borrowed("A"));
+
+        database.classes.insert(Cow::Borrowed("A"), a);
+        database.classes.insert(Cow::Borrowed("B"), b);
+
+        assert!(database.find_property("A", "Missing").is_none());
+    }
+
+    #[test]
+    fn canonicalize_coerces_ambiguous_value() {
+        let mut database = ReflectionDatabase::new();
+        let mut class = ClassDescriptor::new("Frobulator");
+
+        let mut property = PropertyDescriptor::new("Count");
+        property.value_type = PropertyType::Data(VariantType::Int64);
+        class.properties.insert(Cow::Borrowed("Count"), property);
+
+        database.classes.insert(Cow::Borrowed("Frobulator"), class);
+
+        let resolved = database
+            .canonicalize("Frobulator", "Count", Variant::Int32(7))
+            .unwrap();
+        assert!(matches!(resolved, Variant::Int64(7)));
+    }
+
+    #[test]
+    fn canonicalize_rejects_lossy_value() {
+        let mut database = ReflectionDatabase::new();
+        let mut class = ClassDescriptor::new("Frobulator");
+
+        let mut property = PropertyDescriptor::new("Count");
+        property.value_type = PropertyType::Data(VariantType::Int32);
+        class.properties.insert(Cow::Borrowed("Count"), property);
+
+        database.classes.insert(Cow::Borrowed("Frobulator"), class);
+
+        let result = database.canonicalize("Frobulator", "Count", Variant::Float32(1.5));
+        assert!(matches!(result, Err(ResolveError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn canonicalize_passes_through_unknown_property() {
+        let database = ReflectionDatabase::new();
+
+        let resolved = database
+            .canonicalize("Frobulator", "Count", Variant::Int32(7))
+            .unwrap();
+        assert!(matches!(resolved, Variant::Int32(7)));
+    }
+
+    #[test]
+    fn canonicalize_rejects_unknown_enum_item() {
+        let mut database = ReflectionDatabase::new();
+
+        let mut material = EnumDescriptor::new("Material");
+        material.items.insert(Cow::Borrowed("Plastic"), 256);
+        database.enums.insert(Cow::Borrowed("Material"), material);
+
+        let mut class = ClassDescriptor::new("Part");
+        let mut property = PropertyDescriptor::new("Material");
+        property.value_type = PropertyType::Enum(Cow::Borrowed("Material"));
+        class.properties.insert(Cow::Borrowed("Material"), property);
+        database.classes.insert(Cow::Borrowed("Part"), class);
+
+        let result = database.canonicalize("Part", "Material", Variant::Int32(999));
+        assert!(matches!(result, Err(ResolveError::UnknownEnumItem { .. })));
+    }
+
+    #[test]
+    fn serialization_is_sorted_regardless_of_insertion_order() {
+        let mut forward = ReflectionDatabase::new();
+        forward.classes.insert(Cow::Borrowed("A"), ClassDescriptor::new("A"));
+        forward.classes.insert(Cow::Borrowed("B"), ClassDescriptor::new("B"));
+        forward.classes.insert(Cow::Borrowed("C"), ClassDescriptor::new("C"));
+
+        let mut backward = ReflectionDatabase::new();
+        backward.classes.insert(Cow::Borrowed("C"), ClassDescriptor::new("C"));
+        backward.classes.insert(Cow::Borrowed("B"), ClassDescriptor::new("B"));
+        backward.classes.insert(Cow::Borrowed("A"), ClassDescriptor::new("A"));
+
+        let forward_json = serde_json::to_string(&forward).unwrap();
+        let backward_json = serde_json::to_string(&backward).unwrap();
+
+        assert_eq!(forward_json, backward_json);
+    }
+}